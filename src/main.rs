@@ -1,16 +1,242 @@
-use rusqlite::{params, Connection, Result};
+use rusqlite::functions::{Aggregate, Context, FunctionFlags};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::io::{self, Write};
+use std::str::FromStr;
 use chrono::{Local, Days};
 
+/// A wall-clock time of day, always a valid `00:00`..=`23:59`.
+///
+/// Parsing (`FromStr`) rejects anything out of range instead of silently
+/// coercing it to midnight, and `Display` always re-renders canonical `HH:MM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClockTime {
+    hour: u8,
+    minute: u8,
+}
+
+impl ClockTime {
+    fn new(hour: u8, minute: u8) -> Option<Self> {
+        if hour <= 23 && minute <= 59 {
+            Some(ClockTime { hour, minute })
+        } else {
+            None
+        }
+    }
+
+    /// Minutes since midnight, in `0..=1439`.
+    fn to_minutes(self) -> i32 {
+        self.hour as i32 * 60 + self.minute as i32
+    }
+
+    /// The `Duration` from this time until `other`, assuming `other` falls on
+    /// the same or the following day (i.e. it wraps across midnight if `other`
+    /// is not later in the day than `self`).
+    fn duration_until(self, other: ClockTime) -> Duration {
+        let start = self.to_minutes();
+        let mut end = other.to_minutes();
+        if end <= start {
+            end += 24 * 60;
+        }
+        Duration::from_minutes(end - start)
+    }
+}
+
+#[derive(Debug)]
+struct ParseClockTimeError(String);
+
+impl fmt::Display for ParseClockTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is not a valid time; expected HH:MM with hour 00-23 and minute 00-59",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseClockTimeError {}
+
+impl FromStr for ClockTime {
+    type Err = ParseClockTimeError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split(':').collect::<Vec<&str>>().as_slice() {
+            [h, m] => match (h.parse::<u8>(), m.parse::<u8>()) {
+                (Ok(h), Ok(m)) => ClockTime::new(h, m).ok_or_else(|| ParseClockTimeError(s.to_string())),
+                _ => Err(ParseClockTimeError(s.to_string())),
+            },
+            _ => Err(ParseClockTimeError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ClockTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl Serialize for ClockTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ClockTime {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl FromSql for ClockTime {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?.parse().map_err(|e| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+impl ToSql for ClockTime {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+/// A span of time expressed as whole hours and minutes, with the invariant
+/// `minutes < 60` always upheld by construction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Duration {
+    hours: u32,
+    minutes: u32,
+}
+
+impl Duration {
+    /// No one sleeps this long; bounds the value parsed from user input so
+    /// `hours * 60` below can't overflow `u32`.
+    const MAX_HOURS: u32 = 999;
+
+    fn new(hours: u32, minutes: u32) -> Self {
+        let total = hours * 60 + minutes;
+        Duration { hours: total / 60, minutes: total % 60 }
+    }
+
+    fn from_minutes(total_minutes: i32) -> Self {
+        Duration::new(0, total_minutes.max(0) as u32)
+    }
+
+    fn as_minutes(self) -> i32 {
+        (self.hours * 60 + self.minutes) as i32
+    }
+}
+
+impl std::ops::Add for Duration {
+    type Output = Duration;
+
+    fn add(self, rhs: Duration) -> Duration {
+        Duration::new(self.hours + rhs.hours, self.minutes + rhs.minutes)
+    }
+}
+
+#[derive(Debug)]
+struct ParseDurationError(String);
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is not a valid duration; expected HH:MM with minute 00-59 and hours up to {}", self.0, Duration::MAX_HOURS)
+    }
+}
+
+impl std::error::Error for ParseDurationError {}
+
+impl FromStr for Duration {
+    type Err = ParseDurationError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split(':').collect::<Vec<&str>>().as_slice() {
+            [h, m] => match (h.parse::<u32>(), m.parse::<u32>()) {
+                (Ok(h), Ok(m)) if m < 60 && h <= Duration::MAX_HOURS => Ok(Duration::new(h, m)),
+                _ => Err(ParseDurationError(s.to_string())),
+            },
+            _ => Err(ParseDurationError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hours, self.minutes)
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// User-configurable sleep goals and report settings, loaded from
+/// `~/.config/sleep_tracker/config.toml`. Any field missing from the file,
+/// or the file itself being absent, falls back to its hardcoded default.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct Config {
+    target_sleep_hours: f64,
+    target_efficiency_percent: f64,
+    db_path: String,
+    report_window_days_short: i32,
+    report_window_days_long: i32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            target_sleep_hours: 8.0,
+            target_efficiency_percent: 85.0,
+            db_path: "tracker.sqlite".to_string(),
+            report_window_days_short: 7,
+            report_window_days_long: 30,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file from `~/.config/sleep_tracker/config.toml`,
+    /// falling back to `Config::default()` if it is missing, unreadable, or malformed.
+    fn load() -> Config {
+        config_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+}
+
+fn config_path() -> Option<std::path::PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(std::path::PathBuf::from(home).join(".config/sleep_tracker/config.toml"))
+}
+
+/// Renders whether a metric meets its configured goal.
+fn goal_indicator(meets_goal: bool) -> &'static str {
+    if meets_goal { "✓ meets goal" } else { "✗ below goal" }
+}
+
 /// Represents a single sleep tracking entry with all relevant metrics
 #[derive(Debug, Serialize, Deserialize)]
 struct Answer {
     id: i64,
     entry_date: String,
-    bedtime: String,
-    wake_time_target: String,
-    wake_time_actual: String,
+    bedtime: ClockTime,
+    wake_time_target: ClockTime,
+    wake_time_actual: ClockTime,
     notes: String,
     nap_minutes: i32,
     sleep_quality_score: i32,
@@ -26,6 +252,8 @@ enum AppError {
     UserExit,
     Database(rusqlite::Error),
     Io(io::Error),
+    Serialization(serde_json::Error),
+    Parse(String),
 }
 
 impl From<rusqlite::Error> for AppError {
@@ -40,6 +268,12 @@ impl From<io::Error> for AppError {
     }
 }
 
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::Serialization(err)
+    }
+}
+
 /// Data Access Object for managing sleep tracking entries in SQLite database
 struct AnswerDao {
     conn: Connection,
@@ -48,6 +282,7 @@ struct AnswerDao {
 impl AnswerDao {
     fn new(db_path: &str) -> Result<Self> {
         let conn = Connection::open(db_path)?;
+        register_efficiency_functions(&conn)?;
         Ok(AnswerDao { conn })
     }
 
@@ -75,9 +310,9 @@ impl AnswerDao {
     fn insert(
         &self,
         entry_date: &str,
-        bedtime: &str,
-        wake_target: &str,
-        wake_actual: &str,
+        bedtime: &ClockTime,
+        wake_target: &ClockTime,
+        wake_actual: &ClockTime,
         nap: i32,
         quality: i32,
         total: i32,
@@ -171,6 +406,278 @@ impl AnswerDao {
         }
         Ok(answers)
     }
+
+    /// Computes sleep efficiency for a single (not-yet-stored) night via the
+    /// `sleep_efficiency` SQL scalar function, so single-entry and averaged
+    /// efficiency always agree on the formula.
+    fn compute_efficiency(&self, total: i32, awake: i32, latency: i32) -> Result<f64> {
+        self.conn.query_row(
+            "SELECT sleep_efficiency(?1, ?2, ?3)",
+            params![total, awake, latency],
+            |row| row.get(0),
+        )
+    }
+
+    /// Average sleep efficiency across entries since `days` ago, computed
+    /// entirely in SQL via the `avg_efficiency` aggregate function.
+    fn get_average_efficiency(&self, days: i32) -> Result<f64> {
+        let cutoff_date = Local::now()
+            .checked_sub_days(Days::new(days as u64))
+            .unwrap()
+            .format("%Y-%m-%d")
+            .to_string();
+
+        self.conn.query_row(
+            "SELECT avg_efficiency(total_sleep_minutes, awake_minutes, sleep_latency_minutes)
+             FROM answer WHERE entry_date >= ?1",
+            [cutoff_date],
+            |row| row.get(0),
+        )
+    }
+
+    /// Inserts `answer`, or updates the existing row with the same `id` if one
+    /// already exists. `entry_date` is not unique (a night can be corrected or
+    /// a second entry logged before midnight), so only `id` — carried through
+    /// `export_json`/`export_csv` and back — can safely identify "the same
+    /// night" on re-import; keying on `entry_date` would silently collapse
+    /// same-day entries onto one row.
+    fn upsert_by_id(&self, answer: &Answer) -> Result<()> {
+        let exists: bool = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM answer WHERE id = ?1",
+                [answer.id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+
+        if exists {
+            self.conn.execute(
+                "UPDATE answer SET
+                    entry_date = ?1, bedtime = ?2, wake_time_target = ?3, wake_time_actual = ?4,
+                    nap_minutes = ?5, sleep_quality_score = ?6, total_sleep_minutes = ?7,
+                    awake_minutes = ?8, sleep_latency_minutes = ?9, wake_count = ?10, notes = ?11
+                 WHERE id = ?12",
+                params![
+                    answer.entry_date, answer.bedtime, answer.wake_time_target, answer.wake_time_actual,
+                    answer.nap_minutes, answer.sleep_quality_score, answer.total_sleep_minutes,
+                    answer.awake_minutes, answer.sleep_latency_minutes, answer.wake_count,
+                    answer.notes, answer.id
+                ],
+            )?;
+        } else {
+            self.conn.execute(
+                "INSERT INTO answer (
+                    id, entry_date, bedtime, wake_time_target, wake_time_actual,
+                    nap_minutes, sleep_quality_score, total_sleep_minutes,
+                    awake_minutes, sleep_latency_minutes, wake_count, notes
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    answer.id, answer.entry_date, answer.bedtime, answer.wake_time_target,
+                    answer.wake_time_actual, answer.nap_minutes, answer.sleep_quality_score,
+                    answer.total_sleep_minutes, answer.awake_minutes, answer.sleep_latency_minutes,
+                    answer.wake_count, answer.notes
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Dumps every row as a pretty-printed JSON array, in `list_all` order.
+    fn export_json(&self, path: &str) -> std::result::Result<usize, AppError> {
+        let answers = self.list_all()?;
+        let json = serde_json::to_string_pretty(&answers)?;
+        std::fs::write(path, json)?;
+        Ok(answers.len())
+    }
+
+    /// Dumps every row as CSV, with a header matching the `list_all` column order.
+    fn export_csv(&self, path: &str) -> std::result::Result<usize, AppError> {
+        let answers = self.list_all()?;
+        let mut csv = String::from(
+            "id,entry_date,bedtime,wake_time_target,wake_time_actual,\
+             nap_minutes,sleep_quality_score,total_sleep_minutes,\
+             awake_minutes,sleep_latency_minutes,wake_count,notes\n",
+        );
+        for a in &answers {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                a.id,
+                csv_field(&a.entry_date),
+                a.bedtime,
+                a.wake_time_target,
+                a.wake_time_actual,
+                a.nap_minutes,
+                a.sleep_quality_score,
+                a.total_sleep_minutes,
+                a.awake_minutes,
+                a.sleep_latency_minutes,
+                a.wake_count,
+                csv_field(&a.notes),
+            ));
+        }
+        std::fs::write(path, csv)?;
+        Ok(answers.len())
+    }
+
+    /// Upserts every entry from a JSON array previously produced by `export_json`.
+    fn import_json(&self, path: &str) -> std::result::Result<usize, AppError> {
+        let data = std::fs::read_to_string(path)?;
+        let answers: Vec<Answer> = serde_json::from_str(&data)?;
+        for answer in &answers {
+            self.upsert_by_id(answer)?;
+        }
+        Ok(answers.len())
+    }
+
+    /// Upserts every entry from a CSV file previously produced by `export_csv`.
+    fn import_csv(&self, path: &str) -> std::result::Result<usize, AppError> {
+        let data = std::fs::read_to_string(path)?;
+        let mut records = parse_csv_records(&data).into_iter();
+        records.next(); // header row
+
+        let mut count = 0;
+        for fields in records {
+            if fields.len() != 12 {
+                continue;
+            }
+
+            let answer = Answer {
+                id: fields[0].parse().unwrap_or(0),
+                entry_date: fields[1].clone(),
+                bedtime: fields[2]
+                    .parse()
+                    .map_err(|e: ParseClockTimeError| AppError::Parse(e.to_string()))?,
+                wake_time_target: fields[3]
+                    .parse()
+                    .map_err(|e: ParseClockTimeError| AppError::Parse(e.to_string()))?,
+                wake_time_actual: fields[4]
+                    .parse()
+                    .map_err(|e: ParseClockTimeError| AppError::Parse(e.to_string()))?,
+                nap_minutes: fields[5].parse().unwrap_or(0),
+                sleep_quality_score: fields[6].parse().unwrap_or(0),
+                total_sleep_minutes: fields[7].parse().unwrap_or(0),
+                awake_minutes: fields[8].parse().unwrap_or(0),
+                sleep_latency_minutes: fields[9].parse().unwrap_or(0),
+                wake_count: fields[10].parse().unwrap_or(0),
+                notes: fields[11].clone(),
+            };
+
+            self.upsert_by_id(&answer)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Quotes a CSV field if it contains a comma, quote, or newline, doubling any
+/// embedded quotes per the usual CSV escaping convention.
+fn csv_field(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits a whole CSV file into records of fields, honoring `csv_field`'s
+/// quoting convention. Tracks quote state across the entire file rather than
+/// line by line, so a quoted field containing a literal newline (as `notes`
+/// can) stays intact instead of being torn into two records.
+fn parse_csv_records(data: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else {
+            match c {
+                '"' => in_quotes = true,
+                ',' => fields.push(std::mem::take(&mut field)),
+                '\r' => {}
+                '\n' => {
+                    fields.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut fields));
+                }
+                _ => field.push(c),
+            }
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(fields);
+    }
+    records
+}
+
+/// Registers the `sleep_efficiency` scalar and `avg_efficiency` aggregate
+/// functions on `conn`, mirroring rusqlite's cached-`regexp` pattern so the
+/// efficiency formula lives in one place and can be reused from any query.
+fn register_efficiency_functions(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "sleep_efficiency",
+        3,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let total: f64 = ctx.get(0)?;
+            let awake: f64 = ctx.get(1)?;
+            let latency: f64 = ctx.get(2)?;
+            Ok(sleep_efficiency_formula(total, awake, latency))
+        },
+    )?;
+
+    conn.create_aggregate_function(
+        "avg_efficiency",
+        3,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        AvgEfficiency,
+    )?;
+
+    Ok(())
+}
+
+fn sleep_efficiency_formula(total: f64, awake: f64, latency: f64) -> f64 {
+    let tib = total + awake + latency;
+    if tib == 0.0 { 0.0 } else { total / tib * 100.0 }
+}
+
+/// Running (sum, count) state for the `avg_efficiency` aggregate.
+struct AvgEfficiency;
+
+impl Aggregate<(f64, u32), f64> for AvgEfficiency {
+    fn init(&self, _ctx: &mut Context<'_>) -> Result<(f64, u32)> {
+        Ok((0.0, 0))
+    }
+
+    fn step(&self, ctx: &mut Context<'_>, state: &mut (f64, u32)) -> Result<()> {
+        let total: f64 = ctx.get(0)?;
+        let awake: f64 = ctx.get(1)?;
+        let latency: f64 = ctx.get(2)?;
+        state.0 += sleep_efficiency_formula(total, awake, latency);
+        state.1 += 1;
+        Ok(())
+    }
+
+    fn finalize(&self, _ctx: &mut Context<'_>, state: Option<(f64, u32)>) -> Result<f64> {
+        match state {
+            Some((sum, count)) if count > 0 => Ok(sum / count as f64),
+            _ => Ok(0.0),
+        }
+    }
 }
 
 fn main() {
@@ -179,18 +686,24 @@ fn main() {
         Err(AppError::UserExit) => println!("\nGoodbye! 👋"),
         Err(AppError::Database(e)) => eprintln!("Database error: {}", e),
         Err(AppError::Io(e)) => eprintln!("IO error: {}", e),
+        Err(AppError::Serialization(e)) => eprintln!("Serialization error: {}", e),
+        Err(AppError::Parse(e)) => eprintln!("Parse error: {}", e),
     }
 }
 
 fn run_app() -> std::result::Result<(), AppError> {
-    let dao = AnswerDao::new("tracker.sqlite")?;
+    let config = Config::load();
+    let dao = AnswerDao::new(&config.db_path)?;
     dao.create_table()?;
 
     println!("--- Sleep Tracker ---");
     println!("💡 Tip: Type 'exit', 'quit', or 'q' at any time to stop the program\n");
     println!("1. Enter new sleep data");
     println!("2. View sleep efficiency averages");
-    print!("Choose option (1 or 2): ");
+    println!("3. View sleep-by-minute consistency");
+    println!("4. Export data (JSON/CSV)");
+    println!("5. Import data (JSON/CSV)");
+    print!("Choose option (1-5): ");
     io::stdout().flush()?;
 
     let mut choice = String::new();
@@ -200,11 +713,14 @@ fn run_app() -> std::result::Result<(), AppError> {
     }
 
     match choice.trim() {
-        "1" => enter_sleep_data(&dao)?,
-        "2" => show_efficiency_averages(&dao)?,
+        "1" => enter_sleep_data(&dao, &config)?,
+        "2" => show_efficiency_averages(&dao, &config)?,
+        "3" => show_minute_histogram(&dao, &config)?,
+        "4" => export_data(&dao)?,
+        "5" => import_data(&dao)?,
         _ => {
             println!("Invalid choice. Defaulting to entering new sleep data.");
-            enter_sleep_data(&dao)?;
+            enter_sleep_data(&dao, &config)?;
         }
     }
 
@@ -216,25 +732,26 @@ fn is_exit_command(input: &str) -> bool {
     matches!(input_lower.as_str(), "exit" | "quit" | "q" | "stop")
 }
 
-fn enter_sleep_data(dao: &AnswerDao) -> std::result::Result<(), AppError> {
+fn enter_sleep_data(dao: &AnswerDao, config: &Config) -> std::result::Result<(), AppError> {
     let entry_date = Local::now().format("%Y-%m-%d").to_string();
 
     println!("\n--- Enter Sleep Data for {} ---", entry_date);
     println!("💡 Reminder: Type 'exit', 'quit', or 'q' at any prompt to stop");
 
-    let bedtime = get_input("What time did you go to bed last night? (HH:MM): ")?;
-    let wake_target = get_input("What time did you plan to wake up? (HH:MM): ")?;
-    let wake_actual = get_input("What time did you actually get out of bed? (HH:MM): ")?;
+    let bedtime = get_clock_time_input("What time did you go to bed last night? (HH:MM): ")?;
+    let wake_target = get_clock_time_input("What time did you plan to wake up? (HH:MM): ")?;
+    let wake_actual = get_clock_time_input("What time did you actually get out of bed? (HH:MM): ")?;
     let nap = get_number_input("How many minutes did you nap yesterday? (0 if none): ")?;
     let quality = get_number_input("Rate your sleep quality (1-5): ")?;
-    let total_sleep_str = get_input("Total sleep time? (HH:MM): ")?;
+    let total_sleep = get_duration_input("Total sleep time? (HH:MM): ")?;
     let awake = get_number_input("Minutes awake during night: ")?;
     let latency = get_number_input("Minutes to fall asleep: ")?;
     let wake_count = get_number_input("How many times did you wake up: ")?;
     let notes = get_input("Any additional notes (optional): ")?;
 
-    let total_min = to_minutes(&total_sleep_str);
-    let efficiency = calc_efficiency(total_min, awake, latency);
+    let total_min = total_sleep.as_minutes();
+    let efficiency = dao.compute_efficiency(total_min, awake, latency)?;
+    let planned_window = bedtime.duration_until(wake_target);
 
     let id = dao.insert(
         &entry_date,
@@ -250,54 +767,252 @@ fn enter_sleep_data(dao: &AnswerDao) -> std::result::Result<(), AppError> {
         &notes,
     )?;
 
+    let sleep_hours = total_min as f64 / 60.0;
+    let efficiency_pass = efficiency >= config.target_efficiency_percent;
+    let sleep_pass = sleep_hours >= config.target_sleep_hours;
+
     println!("\n✓ Sleep data saved successfully!");
     println!("Entry ID: {}", id);
-    println!("Sleep Efficiency: {:.1}%", efficiency);
-    println!("Total Sleep: {:.1} hours", total_min as f64 / 60.0);
+    println!(
+        "Sleep Efficiency: {:.1}% (goal {:.0}%) {}",
+        efficiency, config.target_efficiency_percent, goal_indicator(efficiency_pass)
+    );
+    println!(
+        "Total Sleep: {:.1} hours (goal {:.1}h) {}",
+        sleep_hours, config.target_sleep_hours, goal_indicator(sleep_pass)
+    );
+    println!("Planned Sleep Window: {} ({:.1} hours)", planned_window, planned_window.as_minutes() as f64 / 60.0);
 
     Ok(())
 }
 
-fn show_efficiency_averages(dao: &AnswerDao) -> std::result::Result<(), AppError> {
+fn show_efficiency_averages(dao: &AnswerDao, config: &Config) -> std::result::Result<(), AppError> {
     println!("\n--- Sleep Efficiency Averages ---");
 
-    let entries_7_days = dao.get_recent_entries(7)?;
-    if !entries_7_days.is_empty() {
-        let avg_efficiency_7 = calculate_average_efficiency(&entries_7_days);
-        let avg_quality_7    = calculate_average_quality(&entries_7_days);
-        let avg_sleep_7      = calculate_average_sleep_hours(&entries_7_days);
-        let avg_sleep_nap_7  = calculate_average_total_sleep_with_nap(&entries_7_days);
-
-        println!("Last 7 days ({} entries):", entries_7_days.len());
-        println!("  Average Sleep Efficiency:      {:.1}%", avg_efficiency_7);
-        println!("  Average Sleep Quality:         {:.1}/5", avg_quality_7);
-        println!("  • Avg Night-only Sleep:        {:.1} hours", avg_sleep_7);
-        println!("  • Avg Total Sleep (incl. naps):{:.1} hours", avg_sleep_nap_7);
+    let short_days = config.report_window_days_short;
+    let entries_short = dao.get_recent_entries(short_days)?;
+    if !entries_short.is_empty() {
+        let avg_efficiency_short = dao.get_average_efficiency(short_days)?;
+        let avg_quality_short    = calculate_average_quality(&entries_short);
+        let avg_sleep_short      = calculate_average_sleep_hours(&entries_short);
+        let avg_sleep_nap_short  = calculate_average_total_sleep_with_nap(&entries_short);
+
+        println!("Last {} days ({} entries):", short_days, entries_short.len());
+        println!(
+            "  Average Sleep Efficiency:      {:.1}% {}",
+            avg_efficiency_short,
+            goal_indicator(avg_efficiency_short >= config.target_efficiency_percent)
+        );
+        println!("  Average Sleep Quality:         {:.1}/5", avg_quality_short);
+        println!(
+            "  • Avg Night-only Sleep:        {:.1} hours {}",
+            avg_sleep_short,
+            goal_indicator(avg_sleep_short >= config.target_sleep_hours)
+        );
+        println!("  • Avg Total Sleep (incl. naps):{:.1} hours", avg_sleep_nap_short);
     } else {
-        println!("Last 7 days: No data available");
+        println!("Last {} days: No data available", short_days);
     }
 
-    let entries_30_days = dao.get_recent_entries(30)?;
-    if !entries_30_days.is_empty() {
-        let avg_efficiency_30 = calculate_average_efficiency(&entries_30_days);
-        let avg_quality_30    = calculate_average_quality(&entries_30_days);
-        let avg_sleep_30      = calculate_average_sleep_hours(&entries_30_days);
-        let avg_sleep_nap_30  = calculate_average_total_sleep_with_nap(&entries_30_days);
+    let long_days = config.report_window_days_long;
+    let entries_long = dao.get_recent_entries(long_days)?;
+    if !entries_long.is_empty() {
+        let avg_efficiency_long = dao.get_average_efficiency(long_days)?;
+        let avg_quality_long    = calculate_average_quality(&entries_long);
+        let avg_sleep_long      = calculate_average_sleep_hours(&entries_long);
+        let avg_sleep_nap_long  = calculate_average_total_sleep_with_nap(&entries_long);
 
-        println!("\nLast 30 days ({} entries):", entries_30_days.len());
-        println!("  Average Sleep Efficiency:      {:.1}%", avg_efficiency_30);
-        println!("  Average Sleep Quality:         {:.1}/5", avg_quality_30);
-        println!("  • Avg Night-only Sleep:        {:.1} hours", avg_sleep_30);
-        println!("  • Avg Total Sleep (incl. naps):{:.1} hours", avg_sleep_nap_30);
+        println!("\nLast {} days ({} entries):", long_days, entries_long.len());
+        println!(
+            "  Average Sleep Efficiency:      {:.1}% {}",
+            avg_efficiency_long,
+            goal_indicator(avg_efficiency_long >= config.target_efficiency_percent)
+        );
+        println!("  Average Sleep Quality:         {:.1}/5", avg_quality_long);
+        println!(
+            "  • Avg Night-only Sleep:        {:.1} hours {}",
+            avg_sleep_long,
+            goal_indicator(avg_sleep_long >= config.target_sleep_hours)
+        );
+        println!("  • Avg Total Sleep (incl. naps):{:.1} hours", avg_sleep_nap_long);
     } else {
-        println!("Last 30 days: No data available");
+        println!("Last {} days: No data available", long_days);
+    }
+
+    println!("\nPress Enter to continue or type 'exit' to stop...");
+    let _ = get_input("")?;
+    Ok(())
+}
+
+/// Reports which clock-minutes you are most reliably asleep, based on the
+/// bedtime -> wake_time_actual window of each entry in the last
+/// `config.report_window_days_long` days (30 by default).
+///
+/// When `total_sleep_minutes` implies the person wasn't asleep for the whole
+/// bedtime-to-wake window (e.g. they logged time awake or taking a while to
+/// fall asleep), only that many minutes are counted, trimmed evenly off both
+/// ends of the window — we don't know exactly when within it they were awake.
+fn show_minute_histogram(dao: &AnswerDao, config: &Config) -> std::result::Result<(), AppError> {
+    println!("\n--- Sleep-by-Minute Consistency ---");
+
+    let days = config.report_window_days_long;
+    let entries = dao.get_recent_entries(days)?;
+    if entries.is_empty() {
+        println!("Last {} days: No data available", days);
+        println!("\nPress Enter to continue or type 'exit' to stop...");
+        let _ = get_input("")?;
+        return Ok(());
+    }
+
+    let mut counts = [0u32; 1440];
+    let mut nights = 0u32;
+
+    for entry in &entries {
+        let bedtime = entry.bedtime.to_minutes();
+        let mut wake = entry.wake_time_actual.to_minutes();
+        if wake <= bedtime {
+            wake += 24 * 60;
+        }
+        if wake == bedtime {
+            continue;
+        }
+
+        let window_len = wake - bedtime;
+        let asleep_len = entry.total_sleep_minutes.clamp(0, window_len);
+        let deficit = window_len - asleep_len;
+        let trimmed_start = bedtime + deficit / 2;
+        let trimmed_end = wake - (deficit - deficit / 2);
+        if trimmed_end <= trimmed_start {
+            continue;
+        }
+
+        nights += 1;
+        let mut minute = trimmed_start;
+        while minute < trimmed_end {
+            counts[(minute % 1440) as usize] += 1;
+            minute += 1;
+        }
+    }
+
+    if nights == 0 {
+        println!("Last {} days: No usable bedtime/wake windows (all zero-length)", days);
+        println!("\nPress Enter to continue or type 'exit' to stop...");
+        let _ = get_input("")?;
+        return Ok(());
+    }
+
+    let (peak_minute, peak_count) = counts
+        .iter()
+        .enumerate()
+        .max_by_key(|&(_, &count)| count)
+        .map(|(minute, &count)| (minute, count))
+        .unwrap();
+
+    let threshold = (nights as f64 * 0.8).ceil() as u32;
+    println!("Last {} days ({} usable nights):", days, nights);
+    println!(
+        "  Peak minute asleep: {} ({} of {} nights)",
+        minute_to_hhmm(peak_minute),
+        peak_count,
+        nights
+    );
+
+    match core_sleep_window(&counts, threshold) {
+        Some((start, end)) => println!(
+            "  Core sleep window (>={:.0}% of nights asleep): {} - {}",
+            80.0,
+            minute_to_hhmm(start),
+            minute_to_hhmm(end % 1440)
+        ),
+        None => println!("  Core sleep window: none (no minute reaches the 80% threshold)"),
     }
 
+    print_minute_bar_chart(&counts, nights);
+
     println!("\nPress Enter to continue or type 'exit' to stop...");
     let _ = get_input("")?;
     Ok(())
 }
 
+/// Finds the longest contiguous run of minutes (wrapping across midnight)
+/// where `counts` stays at or above `threshold`.
+fn core_sleep_window(counts: &[u32; 1440], threshold: u32) -> Option<(usize, usize)> {
+    let above: Vec<bool> = counts.iter().map(|&c| c >= threshold).collect();
+    if !above.iter().any(|&b| b) {
+        return None;
+    }
+    if above.iter().all(|&b| b) {
+        return Some((0, 1440));
+    }
+
+    let mut best_start = 0;
+    let mut best_len = 0;
+    let mut i = 0;
+    while i < 1440 {
+        if above[i] {
+            let start = i;
+            let mut len = 0;
+            let mut j = i;
+            while len < 1440 && above[j % 1440] {
+                len += 1;
+                j += 1;
+            }
+            if len > best_len {
+                best_len = len;
+                best_start = start;
+            }
+            i = j.max(i + 1);
+        } else {
+            i += 1;
+        }
+    }
+    Some((best_start, best_start + best_len))
+}
+
+/// Renders an hourly text bar chart of how many nights were asleep during each hour.
+fn print_minute_bar_chart(counts: &[u32; 1440], nights: u32) {
+    println!("\n  Hourly sleep frequency (bar length = avg nights asleep per minute):");
+    for hour in 0..24 {
+        let hour_total: u32 = counts[hour * 60..hour * 60 + 60].iter().sum();
+        let hour_avg = hour_total as f64 / 60.0;
+        let bar = "#".repeat(hour_avg.round() as usize);
+        println!("    {:02}:00 {:<width$} {:.1}/{}", hour, bar, hour_avg, nights, width = nights as usize);
+    }
+}
+
+/// Formats a minute-of-day (0..1440, may be >=1440 after midnight wraparound) as `HH:MM`.
+fn minute_to_hhmm(minute: usize) -> String {
+    let m = minute % 1440;
+    format!("{:02}:{:02}", m / 60, m % 60)
+}
+
+fn export_data(dao: &AnswerDao) -> std::result::Result<(), AppError> {
+    println!("\n--- Export Sleep Data ---");
+    let format = get_input("Export as JSON or CSV? (json/csv): ")?;
+    let is_csv = format.eq_ignore_ascii_case("csv");
+    let default_path = if is_csv { "tracker_export.csv" } else { "tracker_export.json" };
+
+    let path = get_input(&format!("Output file path (default: {}): ", default_path))?;
+    let path = if path.is_empty() { default_path.to_string() } else { path };
+
+    let count = if is_csv { dao.export_csv(&path)? } else { dao.export_json(&path)? };
+
+    println!("\n✓ Exported {} entries to {}", count, path);
+    Ok(())
+}
+
+fn import_data(dao: &AnswerDao) -> std::result::Result<(), AppError> {
+    println!("\n--- Import Sleep Data ---");
+    let format = get_input("Import from JSON or CSV? (json/csv): ")?;
+    let is_csv = format.eq_ignore_ascii_case("csv");
+    let path = get_input("Input file path: ")?;
+
+    let count = if is_csv { dao.import_csv(&path)? } else { dao.import_json(&path)? };
+
+    println!("\n✓ Imported {} entries from {} (upserted by id)", count, path);
+    Ok(())
+}
+
 fn get_input(prompt: &str) -> std::result::Result<String, AppError> {
     print!("{}", prompt);
     io::stdout().flush()?;
@@ -323,39 +1038,24 @@ fn get_number_input(prompt: &str) -> std::result::Result<i32, AppError> {
     }
 }
 
-fn to_minutes(hhmm: &str) -> i32 {
-    match hhmm.split(':').collect::<Vec<&str>>().as_slice() {
-        [h, m] => h.parse().unwrap_or(0) * 60 + m.parse().unwrap_or(0),
-        _ => 0,
+fn get_clock_time_input(prompt: &str) -> std::result::Result<ClockTime, AppError> {
+    loop {
+        let input = get_input(prompt)?;
+        match input.parse::<ClockTime>() {
+            Ok(t) => return Ok(t),
+            Err(e) => println!("{} (or type 'exit' to quit).", e),
+        }
     }
 }
 
-fn calc_window(bedtime: &str, wake_target: &str) -> i32 {
-    let bt = to_minutes(bedtime);
-    let mut wt = to_minutes(wake_target);
-    if wt <= bt { wt += 24 * 60; }
-    wt - bt
-}
-
-fn round_to_2_sig_figs(value: f64) -> f64 {
-    if value == 0.0 { return 0.0; }
-    let scale = value.abs().log10().floor() as i32;
-    let factor = 10.0f64.powi(1 - scale);
-    (value * factor).round() / factor
-}
-
-fn calc_efficiency(sleep: i32, awake: i32, latency: i32) -> f64 {
-    let tib = sleep + awake + latency;
-    if tib == 0 { return 0.0; }
-    round_to_2_sig_figs(sleep as f64 / tib as f64 * 100.0)
-}
-
-fn calculate_average_efficiency(entries: &[Answer]) -> f64 {
-    if entries.is_empty() { return 0.0; }
-    let total: f64 = entries.iter()
-        .map(|e| calc_efficiency(e.total_sleep_minutes, e.awake_minutes, e.sleep_latency_minutes))
-        .sum();
-    total / entries.len() as f64
+fn get_duration_input(prompt: &str) -> std::result::Result<Duration, AppError> {
+    loop {
+        let input = get_input(prompt)?;
+        match input.parse::<Duration>() {
+            Ok(d) => return Ok(d),
+            Err(e) => println!("{} (or type 'exit' to quit).", e),
+        }
+    }
 }
 
 fn calculate_average_quality(entries: &[Answer]) -> f64 {
@@ -381,3 +1081,35 @@ fn calculate_average_total_sleep_with_nap(entries: &[Answer]) -> f64 {
         .sum();
     (total_with_naps as f64 / entries.len() as f64) / 60.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three entries logged on the same `entry_date` (e.g. a correction plus
+    /// a second night before midnight) must all survive an export/import
+    /// round trip, with no duplicates and no data loss.
+    #[test]
+    fn import_json_round_trip_keeps_same_day_entries_distinct() {
+        let midnight: ClockTime = "00:00".parse().unwrap();
+        let source = AnswerDao::new(":memory:").unwrap();
+        source.create_table().unwrap();
+        for _ in 0..3 {
+            source
+                .insert("2026-07-30", &midnight, &midnight, &midnight, 0, 0, 0, 0, 0, 0, "")
+                .unwrap();
+        }
+
+        let path = std::env::temp_dir().join("sleep_tracker_round_trip_test.json");
+        let path = path.to_str().unwrap();
+        source.export_json(path).unwrap();
+
+        let reimported = AnswerDao::new(":memory:").unwrap();
+        reimported.create_table().unwrap();
+        let imported_count = reimported.import_json(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(imported_count, 3);
+        assert_eq!(reimported.list_all().unwrap().len(), 3);
+    }
+}